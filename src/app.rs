@@ -1,17 +1,87 @@
+use std::time::{Duration, Instant};
+
+use accesskit::{Node, NodeId, Role, Tree, TreeUpdate};
+use accesskit_winit::{Adapter as AccessKitAdapter, Event as AccessKitEvent, WindowEvent as AccessKitWindowEvent};
 use wgpu::Surface;
 use winit::application::ApplicationHandler;
 use winit::event::{ElementState, KeyEvent, WindowEvent};
-use winit::event_loop::ActiveEventLoop;
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoopBuilder, EventLoopProxy};
 use winit::keyboard::{KeyCode, PhysicalKey};
 #[cfg(platform_linux)]
 use winit::platform::startup_notify::{self, EventLoopExtStartupNotify, WindowAttributesExtStartupNotify};
+#[cfg(platform_macos)]
+use winit::platform::macos::WindowAttributesExtMacOS;
 use winit::window::{Icon, Window, WindowId};
 
 use crate::prelude::*;
 
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
-pub enum UserEvent {}
+/// Events the application sends to itself, e.g. from background tasks on the tokio runtime via
+/// an [`EventLoopProxy`].
+#[derive(Debug)]
+pub enum UserEvent {
+    /// Ask `window` to repaint, either immediately (`after` is zero) or at a future deadline, so
+    /// animation/IO-driven redraws can be coalesced instead of firing immediately under a `Wait`
+    /// control flow.
+    RequestRepaint { window: WindowId, after: Duration },
+    /// An AccessKit adapter wants an initial tree, is relaying an assistive-technology action, or
+    /// is reporting that accessibility was deactivated for one of our windows.
+    AccessKitActionRequest(AccessKitEvent),
+}
+
+impl From<AccessKitEvent> for UserEvent {
+    fn from(event: AccessKitEvent) -> Self {
+        Self::AccessKitActionRequest(event)
+    }
+}
+
+/// Hook invoked just before the `EventLoopBuilder` is built, letting callers apply
+/// platform-specific customization (X11 vs Wayland backend selection on Linux, any-thread flags
+/// on Windows, Android activity wiring, ...) without forking the crate.
+pub type EventLoopBuilderHook = Box<dyn FnOnce(&mut EventLoopBuilder<UserEvent>)>;
+
+bitflags::bitflags! {
+    /// Tracks how the window manager or compositor is currently constraining a window, mirroring
+    /// wezterm's approach of treating e.g. "maximized" as "size is dictated by the window
+    /// manager" rather than a simple on/off toggle. Render/layout code can check this before
+    /// fighting a size the compositor imposed on it.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct WindowFlags: u32 {
+        /// Window is maximized.
+        const MAXIMIZED = 1 << 0;
+        /// Window is fullscreen.
+        const FULLSCREEN = 1 << 1;
+        /// Window is occluded (minimized, on another workspace, etc).
+        const HIDDEN = 1 << 2;
+        /// Window is snapped/tiled against the left edge of the screen.
+        const TILED_LEFT = 1 << 3;
+        /// Window is snapped/tiled against the right edge of the screen.
+        const TILED_RIGHT = 1 << 4;
+        /// Window is snapped/tiled against the top edge of the screen.
+        const TILED_TOP = 1 << 5;
+        /// Window is snapped/tiled against the bottom edge of the screen.
+        const TILED_BOTTOM = 1 << 6;
+    }
+}
+
+/// Standard macOS title bar height, in *logical* points, when using a fullsize-content-view
+/// window. Needs converting to physical pixels via the window's scale factor before it can be
+/// compared against anything in `WindowState`, which otherwise works entirely in physical pixels.
+#[cfg(platform_macos)]
+const MACOS_TITLE_BAR_INSET_LOGICAL: f32 = 28.0;
+
+/// Safe top margin reserved by a unified title bar, in physical pixels matching `size`/`config`.
+/// Zero on platforms other than macOS, since they don't draw under their own title bar.
+fn title_bar_inset_physical(window: &Window) -> f32 {
+    #[cfg(platform_macos)]
+    {
+        MACOS_TITLE_BAR_INSET_LOGICAL * window.scale_factor() as f32
+    }
+    #[cfg(not(platform_macos))]
+    {
+        let _ = window;
+        0.0
+    }
+}
 
 /// State of a winit window.
 struct WindowState<'a> {
@@ -25,17 +95,45 @@ struct WindowState<'a> {
     device: wgpu::Device,
     /// Device command queue handle.
     queue: wgpu::Queue,
+    /// Pipeline used to draw the window's contents. Cached so it isn't rebuilt every frame.
+    render_pipeline: wgpu::RenderPipeline,
+    /// Color the window is cleared to before anything else is drawn.
+    clear_color: wgpu::Color,
+    /// How the window manager is currently constraining this window.
+    flags: WindowFlags,
+    /// Safe top margin reserved by a unified, fullsize-content-view title bar (currently only
+    /// set on macOS), in **physical pixels** matching `size`/`config`. Renderer code should
+    /// offset its content below this inset so it isn't drawn under the traffic-light buttons,
+    /// while still painting `clear_color` behind the title bar itself for a seamless look.
+    title_bar_inset: f32,
+    /// AccessKit adapter publishing this window's accessibility tree to the platform's assistive
+    /// technology API (UIA/AT-SPI/macOS).
+    accesskit_adapter: AccessKitAdapter,
     /// The actual winit Window.
     window: Arc<Window>,
 }
 
 impl<'a> WindowState<'a> {
-    async fn new(_app: &Application<'a>, window: Window) -> Self {
+    async fn new(
+        _app: &Application<'a>,
+        event_loop: &ActiveEventLoop,
+        window: Window,
+        accesskit_proxy: EventLoopProxy<UserEvent>,
+    ) -> Self {
         let window = Arc::new(window);
         let size = window.as_ref().inner_size();
 
+        let accesskit_adapter = AccessKitAdapter::with_event_loop_proxy(event_loop, &window, accesskit_proxy);
+
+        // The browser sandbox only exposes GL (or WebGPU, behind a separate feature); every other
+        // target can go through wgpu's normal backend auto-detection.
+        #[cfg(target_arch = "wasm32")]
+        let backends = wgpu::Backends::GL;
+        #[cfg(not(target_arch = "wasm32"))]
+        let backends = wgpu::Backends::PRIMARY;
+
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::PRIMARY,
+            backends,
             ..Default::default()
         });
 
@@ -51,11 +149,18 @@ impl<'a> WindowState<'a> {
             .await
             .expect("Failed to retrieve device adapter");
 
+        // WebGL2 only supports the "downlevel" limit set, with its texture-size limits taken from
+        // what the adapter actually reports; every other target can use wgpu's regular defaults.
+        #[cfg(target_arch = "wasm32")]
+        let required_limits = wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits());
+        #[cfg(not(target_arch = "wasm32"))]
+        let required_limits = wgpu::Limits::default();
+
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
+                    required_limits,
                     label: None,
                 },
                 None, // Trace path
@@ -86,17 +191,265 @@ impl<'a> WindowState<'a> {
             }
         };
 
-        Self {
+        surface.configure(&device, &config);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("render pipeline layout"),
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("render pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let title_bar_inset = title_bar_inset_physical(&window);
+
+        let mut window_state = Self {
             window,
             surface,
             device,
             queue,
             config,
             size,
+            render_pipeline,
+            clear_color: wgpu::Color::BLACK,
+            flags: WindowFlags::empty(),
+            title_bar_inset,
+            accesskit_adapter,
+        };
+
+        // Pick up whatever maximized/fullscreen/tiled state the window already has (e.g. the OS
+        // restored it maximized or tiled on launch) instead of reporting "not constrained" until
+        // the first resize or scale-factor change.
+        window_state.update_flags();
+
+        window_state
+    }
+
+    /// Render a single frame: clear the surface to `clear_color` and draw the full-screen
+    /// triangle with `render_pipeline`.
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let output = self.surface.get_current_texture()?;
+        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("render encoder") });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("render pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.clear_color),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_pipeline(&self.render_pipeline);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+
+    /// Reconfigure the render surface for a new window size. Ignored if `new_size` has a zero
+    /// width or height, since `wgpu` does not allow configuring a surface with zero area.
+    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        if new_size.width == 0 || new_size.height == 0 {
+            return;
+        }
+
+        self.size = new_size;
+        self.config.width = new_size.width;
+        self.config.height = new_size.height;
+        self.surface.configure(&self.device, &self.config);
+    }
+
+    /// Refresh `flags` from the window's current maximized/fullscreen/tiled state.
+    fn update_flags(&mut self) {
+        self.flags.set(WindowFlags::MAXIMIZED, self.window.is_maximized());
+        self.flags.set(WindowFlags::FULLSCREEN, self.window.fullscreen().is_some());
+        self.update_tiled_flags();
+    }
+
+    /// Heuristically detect which edges of the window are snapped/tiled against a monitor edge,
+    /// the way wezterm infers tiling on platforms that don't report it directly: a window that
+    /// touches a monitor edge and spans roughly half the monitor's size on that axis (the
+    /// footprint a WM's edge/quadrant tiling actually produces) is treated as tiled against it,
+    /// unless the window is already maximized or fullscreen. This is still a heuristic, not a
+    /// guarantee — an ordinary floating window of just the right size positioned at an edge can
+    /// still false-positive — but requiring a roughly-half-monitor span rules out the common case
+    /// of a merely corner-positioned, arbitrarily-sized floating window.
+    fn update_tiled_flags(&mut self) {
+        const TILED_FLAGS: WindowFlags =
+            WindowFlags::TILED_LEFT.union(WindowFlags::TILED_RIGHT).union(WindowFlags::TILED_TOP).union(WindowFlags::TILED_BOTTOM);
+
+        self.flags.remove(TILED_FLAGS);
+
+        if self.flags.intersects(WindowFlags::MAXIMIZED | WindowFlags::FULLSCREEN) {
+            return;
+        }
+
+        let (Some(monitor), Ok(outer_position)) = (self.window.current_monitor(), self.window.outer_position()) else {
+            return;
+        };
+        let outer_size = self.window.outer_size();
+        let monitor_position = monitor.position();
+        let monitor_size = monitor.size();
+
+        // Allow a small amount of slop: window managers often leave a hairline gap between tiled
+        // windows and the screen edge.
+        const EDGE_EPSILON: i32 = 2;
+
+        let touches_left = (outer_position.x - monitor_position.x).abs() <= EDGE_EPSILON;
+        let touches_top = (outer_position.y - monitor_position.y).abs() <= EDGE_EPSILON;
+        let touches_right =
+            ((monitor_position.x + monitor_size.width as i32) - (outer_position.x + outer_size.width as i32)).abs() <= EDGE_EPSILON;
+        let touches_bottom =
+            ((monitor_position.y + monitor_size.height as i32) - (outer_position.y + outer_size.height as i32)).abs() <= EDGE_EPSILON;
+
+        // Ordinary floating windows are routinely positioned flush against a monitor corner
+        // without being anywhere near monitor size, so "touches an edge and isn't full monitor
+        // size" alone would misclassify them as tiled. Window managers that edge-tile snap to
+        // roughly half (or, for quadrant tiling, half in both axes) the monitor's size on the
+        // axis being split, so require the window to actually fall in that band before calling
+        // it tiled on that axis.
+        const TILED_FRACTION_MIN: f32 = 0.35;
+        const TILED_FRACTION_MAX: f32 = 0.65;
+
+        let width_fraction = outer_size.width as f32 / monitor_size.width as f32;
+        let height_fraction = outer_size.height as f32 / monitor_size.height as f32;
+
+        let split_by_width = (TILED_FRACTION_MIN..=TILED_FRACTION_MAX).contains(&width_fraction);
+        let split_by_height = (TILED_FRACTION_MIN..=TILED_FRACTION_MAX).contains(&height_fraction);
+
+        self.flags.set(WindowFlags::TILED_LEFT, touches_left && split_by_width);
+        self.flags.set(WindowFlags::TILED_RIGHT, touches_right && split_by_width);
+        self.flags.set(WindowFlags::TILED_TOP, touches_top && split_by_height);
+        self.flags.set(WindowFlags::TILED_BOTTOM, touches_bottom && split_by_height);
+    }
+
+    /// Update whether the window is currently occluded (minimized, on another workspace, etc).
+    fn set_hidden(&mut self, hidden: bool) {
+        self.flags.set(WindowFlags::HIDDEN, hidden);
+    }
+
+    /// Whether the window is currently occluded (minimized, on another workspace, etc) and so not
+    /// worth spending a frame rendering.
+    pub fn is_hidden(&self) -> bool {
+        self.flags.contains(WindowFlags::HIDDEN)
+    }
+
+    /// Whether the window is maximized.
+    pub fn is_maximized(&self) -> bool {
+        self.flags.contains(WindowFlags::MAXIMIZED)
+    }
+
+    /// Whether the window is fullscreen.
+    pub fn is_fullscreen(&self) -> bool {
+        self.flags.contains(WindowFlags::FULLSCREEN)
+    }
+
+    /// Whether any edge of the window is currently snapped/tiled against a monitor edge.
+    pub fn is_tiled(&self) -> bool {
+        self.flags
+            .intersects(WindowFlags::TILED_LEFT | WindowFlags::TILED_RIGHT | WindowFlags::TILED_TOP | WindowFlags::TILED_BOTTOM)
+    }
+
+    /// Which edges of the window are currently snapped/tiled against a monitor edge.
+    pub fn tiled_edges(&self) -> WindowFlags {
+        self.flags & (WindowFlags::TILED_LEFT | WindowFlags::TILED_RIGHT | WindowFlags::TILED_TOP | WindowFlags::TILED_BOTTOM)
+    }
+
+    /// Safe top margin, in physical pixels, to offset content below the title bar on platforms
+    /// with a unified fullsize-content-view title bar (currently just macOS).
+    pub fn title_bar_inset(&self) -> f32 {
+        self.title_bar_inset
+    }
+
+    /// Refresh `title_bar_inset` for the window's current scale factor. Call whenever the scale
+    /// factor changes, since the inset is stored in physical pixels.
+    fn update_title_bar_inset(&mut self) {
+        self.title_bar_inset = title_bar_inset_physical(&self.window);
+    }
+
+    /// Publish an accessibility tree update built by `build` to this window's AccessKit adapter.
+    /// Call whenever on-screen content that assistive technology should see changes; `build` is
+    /// only invoked if a screen reader is actually active.
+    pub fn update_accessibility_tree(&mut self, build: impl FnOnce() -> TreeUpdate) {
+        self.accesskit_adapter.update_if_active(build);
+    }
+
+    /// Translate an incoming AccessKit `ActionRequest` into an application action. There's no
+    /// widget tree yet to route per-node focus/activation to, so only the window-level cases are
+    /// handled directly; everything else is logged with its target node so future widget code has
+    /// a concrete dispatch point to plug into.
+    fn handle_accessibility_action(&mut self, request: accesskit::ActionRequest) {
+        match request.action {
+            accesskit::Action::Focus => self.window.focus_window(),
+            accesskit::Action::Click => {
+                info!("Accessibility click requested on node {:?}", request.target);
+            }
+            action => {
+                info!("Unhandled accessibility action {action:?} requested on node {:?}", request.target);
+            }
         }
     }
 }
 
+/// Minimal initial accessibility tree: a single focusable root node and no children yet. Real
+/// content should call [`WindowState::update_accessibility_tree`] to replace this once it has
+/// something to expose.
+fn default_accessibility_tree() -> TreeUpdate {
+    let root_id = NodeId(0);
+    let mut root = Node::new(Role::Window);
+    root.add_action(accesskit::Action::Focus);
+
+    TreeUpdate {
+        nodes: vec![(root_id, root)],
+        tree: Some(Tree::new(root_id)),
+        focus: root_id,
+    }
+}
+
 /// Represents an Application.
 ///
 /// Can contain multiple windows. Run using `winit::event_loop::EventLoop::run_app()`.
@@ -116,6 +469,13 @@ pub struct Application<'a> {
     windows: HashMap<WindowId, WindowState<'a>>,
     /// Icon used for application.
     icon: Option<Icon>,
+    /// Hook applied to the `EventLoopBuilder` right before it is built.
+    event_loop_hook: Option<EventLoopBuilderHook>,
+    /// Proxy used to send [`UserEvent`]s from outside the event loop, e.g. from tasks on `rt`.
+    proxy: Option<EventLoopProxy<UserEvent>>,
+    /// Repaint requests that are waiting for their deadline to elapse, see
+    /// [`UserEvent::RequestRepaint`].
+    pending_repaints: Vec<(WindowId, Instant)>,
 }
 
 impl<'a> Application<'a> {
@@ -137,9 +497,39 @@ impl<'a> Application<'a> {
             rt,
             icon: None,
             windows: Default::default(),
+            event_loop_hook: None,
+            proxy: None,
+            pending_repaints: Vec::new(),
         })
     }
 
+    /// Register the proxy used to send [`UserEvent`]s to this application from outside the event
+    /// loop. Must be called with the proxy obtained from the built `EventLoop` before it is run.
+    pub fn set_event_loop_proxy(&mut self, proxy: EventLoopProxy<UserEvent>) {
+        self.proxy = Some(proxy);
+    }
+
+    /// Clone of the registered [`EventLoopProxy`], if any, for use by background/async tasks that
+    /// need to ask a window to repaint.
+    pub fn proxy(&self) -> Option<EventLoopProxy<UserEvent>> {
+        self.proxy.clone()
+    }
+
+    /// Register a hook invoked just before the `EventLoopBuilder` is built, see
+    /// [`EventLoopBuilderHook`].
+    pub fn with_event_loop_hook(mut self, hook: impl FnOnce(&mut EventLoopBuilder<UserEvent>) + 'static) -> Self {
+        self.event_loop_hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Apply the registered event loop hook, if any, to `builder`. Must be called before
+    /// `builder.build()`.
+    pub fn apply_event_loop_hook(&mut self, builder: &mut EventLoopBuilder<UserEvent>) {
+        if let Some(hook) = self.event_loop_hook.take() {
+            hook(builder);
+        }
+    }
+
     /// Set application icon. Also sets icon for every window in the application.
     pub fn with_icon(mut self, icon: &[u8]) -> Self {
         info!("Loading icon");
@@ -186,10 +576,25 @@ impl<'a> Application<'a> {
             window_attributes = window_attributes.with_tabbing_identifier(&tab_id);
         }
 
+        // Let the wgpu surface extend under the title bar so we can paint a seamless, unified
+        // title bar instead of an opaque strip at the top of the window.
+        #[cfg(platform_macos)]
+        {
+            window_attributes = window_attributes
+                .with_titlebar_transparent(true)
+                .with_fullsize_content_view(true);
+        }
+
         let window = event_loop.create_window(window_attributes)?;
-        let window_state = WindowState::new(self, window).await;
+        let accesskit_proxy = self
+            .proxy
+            .clone()
+            .expect("`set_event_loop_proxy` must be called before creating windows");
+        let window_state = WindowState::new(self, event_loop, window, accesskit_proxy).await;
         let window_id = window_state.window.id();
 
+        window_state.window.request_redraw();
+
         info!("Created new window with id={window_id:?}");
         self.windows.insert(window_id, window_state);
 
@@ -214,9 +619,21 @@ impl<'a> ApplicationHandler<UserEvent> for Application<'a> {
         let _ = self.rt.block_on(self.create_window(event_loop, self.name));
     }
 
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        // On Android the native window (and the wgpu surface backed by it) is destroyed while the
+        // app is suspended, so holding on to it would mean rendering to a dead surface. Drop all
+        // windows here and let `resumed` recreate them once the app is foregrounded again.
+        info!("Suspending application");
+        self.windows.clear();
+    }
+
     fn window_event(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId, event: WindowEvent) {
         info!("Window Event: {event:?}");
 
+        if let Some(window_state) = self.windows.get_mut(&window_id) {
+            window_state.accesskit_adapter.process_event(&window_state.window, &event);
+        }
+
         match event {
             WindowEvent::CloseRequested
             | WindowEvent::KeyboardInput {
@@ -228,7 +645,117 @@ impl<'a> ApplicationHandler<UserEvent> for Application<'a> {
                     },
                 ..
             } => self.close_window(event_loop, &window_id),
+            WindowEvent::Resized(new_size) => {
+                let Some(window_state) = self.windows.get_mut(&window_id) else {
+                    return;
+                };
+
+                window_state.resize(new_size);
+                window_state.update_flags();
+                window_state.window.request_redraw();
+            }
+            WindowEvent::ScaleFactorChanged { .. } => {
+                let Some(window_state) = self.windows.get_mut(&window_id) else {
+                    return;
+                };
+
+                let new_size = window_state.window.inner_size();
+                window_state.resize(new_size);
+                window_state.update_flags();
+                window_state.update_title_bar_inset();
+                window_state.window.request_redraw();
+            }
+            WindowEvent::Occluded(occluded) => {
+                let Some(window_state) = self.windows.get_mut(&window_id) else {
+                    return;
+                };
+
+                window_state.set_hidden(occluded);
+            }
+            WindowEvent::RedrawRequested => {
+                let Some(window_state) = self.windows.get_mut(&window_id) else {
+                    return;
+                };
+
+                // Fully occluded windows aren't visible, so there's nothing to gain from
+                // rendering them.
+                if window_state.is_hidden() {
+                    return;
+                }
+
+                let result = window_state.render();
+
+                match result {
+                    // Don't request another redraw here: the event loop should go idle under
+                    // `ControlFlow::Wait` until something actually changes (input, resize, or a
+                    // `UserEvent::RequestRepaint`) rather than redrawing every frame forever.
+                    Ok(()) => {}
+                    // Surface lost or outdated: reconfigure it and try again next frame.
+                    Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                        window_state.surface.configure(&window_state.device, &window_state.config);
+                        window_state.window.request_redraw();
+                    }
+                    // The system is out of memory: there's nothing left to do but bail out.
+                    Err(wgpu::SurfaceError::OutOfMemory) => {
+                        error!("Out of memory, closing window with id={window_id:?}");
+                        self.close_window(event_loop, &window_id);
+                    }
+                    Err(error) => warn!("Failed to render frame: {error}"),
+                }
+            }
             _ => {}
         }
     }
+
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: UserEvent) {
+        match event {
+            UserEvent::RequestRepaint { window, after } if after.is_zero() => {
+                if let Some(window_state) = self.windows.get(&window) {
+                    window_state.window.request_redraw();
+                }
+            }
+            UserEvent::RequestRepaint { window, after } => {
+                self.pending_repaints.push((window, Instant::now() + after));
+            }
+            UserEvent::AccessKitActionRequest(AccessKitEvent { window_id, window_event }) => {
+                let Some(window_state) = self.windows.get_mut(&window_id) else {
+                    return;
+                };
+
+                match window_event {
+                    AccessKitWindowEvent::InitialTreeRequested => {
+                        window_state.update_accessibility_tree(default_accessibility_tree);
+                    }
+                    AccessKitWindowEvent::ActionRequested(request) => {
+                        window_state.handle_accessibility_action(request);
+                    }
+                    AccessKitWindowEvent::AccessibilityDeactivated => {
+                        info!("Accessibility deactivated for window id={window_id:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let now = Instant::now();
+        let mut next_deadline: Option<Instant> = None;
+
+        self.pending_repaints.retain(|(window_id, deadline)| {
+            if *deadline <= now {
+                if let Some(window_state) = self.windows.get(window_id) {
+                    window_state.window.request_redraw();
+                }
+                false
+            } else {
+                next_deadline = Some(next_deadline.map_or(*deadline, |current| current.min(*deadline)));
+                true
+            }
+        });
+
+        event_loop.set_control_flow(match next_deadline {
+            Some(deadline) => ControlFlow::WaitUntil(deadline),
+            None => ControlFlow::Wait,
+        });
+    }
 }
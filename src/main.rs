@@ -23,8 +23,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut app = Application::new("Envi", &rt)?.with_icon("assets/icon.png");
 
     info!("Creating event loop");
-    let event_loop = EventLoop::<UserEvent>::with_user_event().build()?;
+    let mut event_loop_builder = EventLoop::<UserEvent>::with_user_event();
+    app.apply_event_loop_hook(&mut event_loop_builder);
+    let event_loop = event_loop_builder.build()?;
     event_loop.set_control_flow(winit::event_loop::ControlFlow::Wait);
+    app.set_event_loop_proxy(event_loop.create_proxy());
 
     info!("Running application");
     let _ = event_loop.run_app(&mut app);